@@ -1,178 +1,689 @@
-use std::{fs, env, error::Error};
-
-pub struct Config {
-    pub pattern: String,
-    pub filename: String,
-    pub case_sensitive: bool,
-}
-
-impl Config {
-    pub fn new<T>(mut args: T) -> Result<Config, &'static str> 
-        where T: Iterator<Item = String>
-    {
-        //First arg is program name
-        args.next();
-
-        //Unpack args
-        let pattern = match args.next() {
-            Some(arg) => arg,
-            None => return Err("not enough arguments specified")
-        };
-        let filename = match args.next() {
-            Some(arg) => arg,
-            None => return Err("not enough arguments specified")
-        };
-
-        //Search is case-insensitive iff CASE_INSENSITIVE=1
-        let case_sensitive = match env::var("CASE_INSENSITIVE") {
-            Ok(val) => if val.parse().unwrap_or(0) == 1 {
-                false
-            } else {
-                true
-            },
-            Err(_) => true
-        };
-
-        Ok(Config {pattern, filename, case_sensitive})
-    }
-}
-
-pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
-    let contents = fs::read_to_string(config.filename)?;
-    let results = if config.case_sensitive {
-        search(&config.pattern, &contents)
-    } else {
-        search_case_insensitive(&config.pattern, &contents)
-    };
-
-    for line in results {
-        println!("{}", line);
-    } 
-
-    Ok(())
-}
-
-pub fn search<'a>(pattern: &str, contents: &'a str) -> Vec<&'a str> {
-    contents.lines()
-        .filter(|line| {line.contains(&pattern)})
-        .collect()
-}
-
-pub fn search_case_insensitive<'a>(pattern: &str, contents: &'a str) -> Vec<&'a str> {
-    let pattern = pattern.to_lowercase();
-
-    contents.lines()
-        .filter(|line| {line.to_lowercase().contains(&pattern)})
-        .collect()
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    //Struct for simulating command line arguments to Config
-    struct TestArgs {
-        data: Vec<String>,
-        index: usize
-    }
-
-    impl TestArgs {
-        pub fn new(pattern: &str, filename: &str) -> TestArgs {
-            let mut data = vec![String::new()];
-            data.push(String::from(pattern));
-            data.push(String::from(filename));
-
-            TestArgs{data, index: 0}
-        }
-    }
-
-    impl Iterator for TestArgs {
-        type Item = String;
-
-        fn next(&mut self) -> Option<Self::Item> {
-            if self.index < self.data.len() {
-                self.index += 1;
-                Some(self.data[self.index - 1].to_string())
-            } else {
-                None
-            }
-        }
-    } 
-
-    #[test]
-    fn new_config() {
-        let config = prepare_config("pattern", "filename");
-
-        assert_eq!(config.pattern, "pattern");
-        assert_eq!(config.filename, "filename");
-    }
-
-    #[test]
-    #[should_panic(expected = "not enough arguments")]
-    #[allow(unused_variables)]
-    fn new_config_failure() {
-        //Remove the first argument
-        let args = TestArgs::new("pattern", "filename").filter(|s| *s != String::new());
-
-        let config = Config::new(args).unwrap_or_else(|err| {
-            panic!("could not construct Config: {}", err);
-        });
-    }
-
-    #[test]
-    fn run_success() {
-        let config = prepare_config(" ", "test.txt");
-
-        if let Err(e) = run(config) {
-            panic!("run failed: {}", e);
-        }
-    }
-
-    #[test]
-    #[should_panic(expected = "run failed")]
-    fn run_failure() {
-        let config = prepare_config("pattern", "n0t @ f!L3");
-
-        if let Err(e) = run(config) {
-            panic!("run failed: {}", e);
-        }
-    }
-
-    #[test]
-    fn case_sensitive() {
-        let pattern = "duct";
-        let contents = "\
-Rust:
-safe, fast, productive.
-Pick three.";
-
-        assert_eq!(
-            vec!["safe, fast, productive."],
-            search(pattern, contents)
-        )
-    }
-
-    #[test]
-    fn case_insensitive() {
-        let pattern = "RuSt";
-        let contents = "\
-        Rust:
-safe, fast, productive.
-Pick three.
-Trust me.";
-
-        assert_eq!(
-            vec!["Rust:", "Trust me."],
-            search_case_insensitive(pattern, contents)
-        );
-    }
-
-    fn prepare_config(pattern: &str, filename: &str) -> Config {
-        let args = TestArgs::new(pattern, filename);
-        let config = Config::new(args).unwrap_or_else(|err| {
-            panic!("could not construct Config: {}", err);
-        });
-
-        config
-    }
-}
\ No newline at end of file
+use std::{fs, env, error::Error};
+use std::io::{self, BufRead, IsTerminal};
+use std::path::{Path, PathBuf};
+use regex::RegexBuilder;
+
+const COLOR_START: &str = "\x1b[1;31m";
+const COLOR_END: &str = "\x1b[0m";
+
+#[derive(PartialEq, Debug)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+pub struct Config {
+    pub pattern: String,
+    pub filenames: Vec<String>,
+    pub case_sensitive: bool,
+    pub regex: bool,
+    pub recursive: bool,
+    pub line_number: bool,
+    pub count: bool,
+    pub invert: bool,
+    pub color: ColorChoice,
+}
+
+impl Config {
+    pub fn new<T>(mut args: T) -> Result<Config, &'static str>
+        where T: Iterator<Item = String>
+    {
+        //First arg is program name
+        args.next();
+
+        //Pull out flags, keep the rest as positional args
+        let mut regex = false;
+        let mut recursive = false;
+        let mut line_number = false;
+        let mut count = false;
+        let mut invert = false;
+        let mut color = ColorChoice::Never;
+        let mut positional = Vec::new();
+
+        for arg in args {
+            match arg.as_str() {
+                "-e" | "--regex" => regex = true,
+                "-r" | "--recursive" => recursive = true,
+                "-n" | "--line-number" => line_number = true,
+                "-c" | "--count" => count = true,
+                "-v" | "--invert" => invert = true,
+                _ if arg.starts_with("--color=") => {
+                    color = match &arg["--color=".len()..] {
+                        "always" => ColorChoice::Always,
+                        "never" => ColorChoice::Never,
+                        _ => ColorChoice::Auto
+                    };
+                },
+                _ => positional.push(arg)
+            }
+        }
+        let mut positional = positional.into_iter();
+
+        //Unpack args
+        let pattern = match positional.next() {
+            Some(arg) => arg,
+            None => return Err("not enough arguments specified")
+        };
+        //No filenames, or a bare "-", means "read from stdin"
+        let filenames: Vec<String> = positional.collect();
+        let filenames = if filenames.is_empty() {
+            vec![String::from("-")]
+        } else {
+            filenames
+        };
+
+        //Search is case-insensitive iff CASE_INSENSITIVE=1
+        let case_sensitive = match env::var("CASE_INSENSITIVE") {
+            Ok(val) => val.parse().unwrap_or(0) != 1,
+            Err(_) => true
+        };
+
+        Ok(Config {pattern, filenames, case_sensitive, regex, recursive, line_number, count, invert, color})
+    }
+}
+
+//A file to search, or stdin when the user passed "-" (or no filename at all).
+#[derive(Debug, PartialEq)]
+enum Input {
+    File(PathBuf),
+    Stdin,
+}
+
+pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
+    let files = collect_files(&config.filenames, config.recursive);
+    let multiple = files.len() > 1;
+    let use_color = match config.color {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => io::stdout().is_terminal()
+    };
+    let mut read_any = false;
+
+    for file in &files {
+        let (label, contents) = match file {
+            Input::Stdin => match read_lines(io::stdin().lock()) {
+                Ok(contents) => (String::from("-"), contents),
+                Err(e) => {
+                    eprintln!("warning: could not read from stdin: {}", e);
+                    continue;
+                }
+            },
+            Input::File(path) => match fs::read_to_string(path) {
+                Ok(contents) => (path.display().to_string(), contents),
+                Err(e) => {
+                    eprintln!("warning: could not read '{}': {}", path.display(), e);
+                    continue;
+                }
+            }
+        };
+        read_any = true;
+
+        let results = if config.regex {
+            if config.case_sensitive {
+                search_regex(&config.pattern, &contents, config.invert)?
+            } else {
+                search_regex_case_insensitive(&config.pattern, &contents, config.invert)?
+            }
+        } else if config.case_sensitive {
+            search(&config.pattern, &contents, config.invert)
+        } else {
+            search_case_insensitive(&config.pattern, &contents, config.invert)
+        };
+
+        if config.count {
+            if multiple {
+                println!("{}:{}", label, results.len());
+            } else {
+                println!("{}", results.len());
+            }
+            continue;
+        }
+
+        for (line_no, line, spans) in results {
+            let body = if use_color { highlight(line, &spans) } else { line.to_string() };
+            println!("{}", format_result(&label, multiple, config.line_number, line_no, &body));
+        }
+    }
+
+    if !read_any {
+        return Err(From::from("could not read any of the given files"));
+    }
+
+    Ok(())
+}
+
+//Prefix a result line with its filename (when searching more than one
+//file) and its line number (when `-n` is set), grep-style.
+fn format_result(label: &str, multiple: bool, show_line_number: bool, line_no: usize, body: &str) -> String {
+    let mut prefix = String::new();
+    if multiple {
+        prefix.push_str(&format!("{}:", label));
+    }
+    if show_line_number {
+        prefix.push_str(&format!("{}:", line_no));
+    }
+
+    format!("{}{}", prefix, body)
+}
+
+//Wrap each matched span in ANSI color codes, leaving unmatched text untouched.
+fn highlight(line: &str, spans: &[(usize, usize)]) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut last = 0;
+
+    for &(start, end) in spans {
+        result.push_str(&line[last..start]);
+        result.push_str(COLOR_START);
+        result.push_str(&line[start..end]);
+        result.push_str(COLOR_END);
+        last = end;
+    }
+    result.push_str(&line[last..]);
+
+    result
+}
+
+//Expand filenames into a flat list of inputs, descending into directories
+//when `recursive` is set, warning (rather than failing) on directories
+//we're told not to descend into, and treating "-" as stdin.
+fn collect_files(filenames: &[String], recursive: bool) -> Vec<Input> {
+    let mut files = Vec::new();
+
+    for filename in filenames {
+        if filename == "-" {
+            files.push(Input::Stdin);
+            continue;
+        }
+
+        let path = Path::new(filename);
+        if path.is_dir() {
+            if recursive {
+                walk_dir(path, &mut files);
+            } else {
+                eprintln!("warning: '{}' is a directory (use -r to search recursively)", path.display());
+            }
+        } else {
+            files.push(Input::File(path.to_path_buf()));
+        }
+    }
+
+    files
+}
+
+fn walk_dir(dir: &Path, files: &mut Vec<Input>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            walk_dir(&path, files);
+        } else {
+            files.push(Input::File(path));
+        }
+    }
+}
+
+//Read every line from `reader` into a single owned string, joined by "\n"
+//so it can flow through the same `search*` functions as file contents.
+fn read_lines<R: BufRead>(reader: R) -> io::Result<String> {
+    let mut contents = String::new();
+
+    for line in reader.lines() {
+        contents.push_str(&line?);
+        contents.push('\n');
+    }
+
+    Ok(contents)
+}
+
+//A matching line: its 1-based line number, the line itself, and the
+//byte ranges within it that matched (empty for a line selected by `invert`,
+//since nothing in it matched).
+pub type SearchMatch<'a> = (usize, &'a str, Vec<(usize, usize)>);
+
+pub fn search<'a>(pattern: &str, contents: &'a str, invert: bool) -> Vec<SearchMatch<'a>> {
+    contents.lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let spans: Vec<(usize, usize)> = if pattern.is_empty() {
+                Vec::new()
+            } else {
+                line.match_indices(pattern).map(|(start, m)| (start, start + m.len())).collect()
+            };
+            //An empty pattern matches every line with nothing to highlight,
+            //same as the regex path and the pre-span-based `contains` check.
+            let matched = pattern.is_empty() || !spans.is_empty();
+
+            if matched != invert {
+                Some((i + 1, line, spans))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+pub fn search_case_insensitive<'a>(pattern: &str, contents: &'a str, invert: bool) -> Vec<SearchMatch<'a>> {
+    let pattern_lower: Vec<char> = pattern.to_lowercase().chars().collect();
+
+    contents.lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let spans: Vec<(usize, usize)> = if pattern_lower.is_empty() {
+                Vec::new()
+            } else {
+                case_insensitive_spans(line, &pattern_lower)
+            };
+            //An empty pattern matches every line with nothing to highlight,
+            //same as the regex path and the pre-span-based `contains` check.
+            let matched = pattern_lower.is_empty() || !spans.is_empty();
+
+            if matched != invert {
+                Some((i + 1, line, spans))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+//Find non-overlapping byte ranges in `line` whose lowercased characters equal
+//`pattern_lower`, matching directly against `line` itself rather than a
+//lowercased copy: case folding can change a character's byte length (e.g.
+//Turkish "İ" lowercases to the two-character "i̇"), so offsets taken from a
+//separately-lowercased string aren't safe to slice the original with.
+fn case_insensitive_spans(line: &str, pattern_lower: &[char]) -> Vec<(usize, usize)> {
+    let chars: Vec<(usize, char)> = line.char_indices().collect();
+    let mut spans = Vec::new();
+    let mut start = 0;
+
+    while start < chars.len() {
+        let mut pi = 0;
+        let mut ci = start;
+
+        while pi < pattern_lower.len() && ci < chars.len() {
+            let lowered: Vec<char> = chars[ci].1.to_lowercase().collect();
+            if pi + lowered.len() > pattern_lower.len() || lowered != pattern_lower[pi..pi + lowered.len()] {
+                break;
+            }
+            pi += lowered.len();
+            ci += 1;
+        }
+
+        if pi == pattern_lower.len() {
+            let end = chars.get(ci).map(|&(offset, _)| offset).unwrap_or(line.len());
+            spans.push((chars[start].0, end));
+            start = ci;
+        } else {
+            start += 1;
+        }
+    }
+
+    spans
+}
+
+pub fn search_regex<'a>(pattern: &str, contents: &'a str, invert: bool) -> Result<Vec<SearchMatch<'a>>, Box<dyn Error>> {
+    let re = RegexBuilder::new(pattern).build()?;
+
+    Ok(contents.lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let spans: Vec<(usize, usize)> = re.find_iter(line).map(|m| (m.start(), m.end())).collect();
+
+            if spans.is_empty() == invert {
+                Some((i + 1, line, spans))
+            } else {
+                None
+            }
+        })
+        .collect())
+}
+
+pub fn search_regex_case_insensitive<'a>(pattern: &str, contents: &'a str, invert: bool) -> Result<Vec<SearchMatch<'a>>, Box<dyn Error>> {
+    let re = RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()?;
+
+    Ok(contents.lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let spans: Vec<(usize, usize)> = re.find_iter(line).map(|m| (m.start(), m.end())).collect();
+
+            if spans.is_empty() == invert {
+                Some((i + 1, line, spans))
+            } else {
+                None
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //Struct for simulating command line arguments to Config
+    struct TestArgs {
+        data: Vec<String>,
+        index: usize
+    }
+
+    impl TestArgs {
+        pub fn new(pattern: &str, filenames: &[&str]) -> TestArgs {
+            let mut data = vec![String::new()];
+            data.push(String::from(pattern));
+            data.extend(filenames.iter().map(|f| String::from(*f)));
+
+            TestArgs{data, index: 0}
+        }
+    }
+
+    impl Iterator for TestArgs {
+        type Item = String;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            if self.index < self.data.len() {
+                self.index += 1;
+                Some(self.data[self.index - 1].to_string())
+            } else {
+                None
+            }
+        }
+    }
+
+    #[test]
+    fn new_config() {
+        let config = prepare_config("pattern", "filename");
+
+        assert_eq!(config.pattern, "pattern");
+        assert_eq!(config.filenames, vec!["filename"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "not enough arguments")]
+    #[allow(unused_variables)]
+    fn new_config_failure() {
+        //Only the program name, no pattern
+        let args = TestArgs::new("pattern", &["filename"]).take(1);
+
+        let config = Config::new(args).unwrap_or_else(|err| {
+            panic!("could not construct Config: {}", err);
+        });
+    }
+
+    #[test]
+    fn run_success() {
+        let config = prepare_config(" ", "test.txt");
+
+        if let Err(e) = run(config) {
+            panic!("run failed: {}", e);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "run failed")]
+    fn run_failure() {
+        let config = prepare_config("pattern", "n0t @ f!L3");
+
+        if let Err(e) = run(config) {
+            panic!("run failed: {}", e);
+        }
+    }
+
+    #[test]
+    fn case_sensitive() {
+        let pattern = "duct";
+        let contents = "\
+Rust:
+safe, fast, productive.
+Pick three.";
+        let line = "safe, fast, productive.";
+        let start = line.find(pattern).unwrap();
+
+        assert_eq!(
+            vec![(2, line, vec![(start, start + pattern.len())])],
+            search(pattern, contents, false)
+        )
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let pattern = "RuSt";
+        let contents = "\
+        Rust:
+safe, fast, productive.
+Pick three.
+Trust me.";
+
+        assert_eq!(
+            vec![(1, "Rust:", vec![(0, 4)]), (4, "Trust me.", vec![(1, 5)])],
+            search_case_insensitive(pattern, contents, false)
+        );
+    }
+
+    #[test]
+    fn case_insensitive_highlight_handles_length_changing_case_fold() {
+        //"İ" (U+0130) lowercases to the two-character "i̇", one byte longer
+        //than the original - a regression here panicked inside `highlight`
+        //when spans were computed against that lowercased copy instead of
+        //the original line.
+        let line = "İstanbul xyzduct";
+        let matches = search_case_insensitive("DUCT", line, false);
+        let expected_start = line.rfind("duct").unwrap();
+
+        assert_eq!(matches, vec![(1, line, vec![(expected_start, expected_start + 4)])]);
+
+        let (_, matched_line, spans) = &matches[0];
+        assert_eq!(
+            highlight(matched_line, spans),
+            format!("İstanbul xyz{}duct{}", COLOR_START, COLOR_END)
+        );
+    }
+
+    #[test]
+    fn invert_match() {
+        let pattern = "duct";
+        let contents = "\
+Rust:
+safe, fast, productive.
+Pick three.";
+
+        assert_eq!(
+            vec![(1, "Rust:", vec![]), (3, "Pick three.", vec![])],
+            search(pattern, contents, true)
+        )
+    }
+
+    #[test]
+    fn empty_pattern_matches_every_line_with_no_highlighted_span() {
+        let contents = "\
+Rust:
+safe, fast, productive.
+Pick three.";
+
+        assert_eq!(
+            vec![(1, "Rust:", vec![]), (2, "safe, fast, productive.", vec![]), (3, "Pick three.", vec![])],
+            search("", contents, false)
+        );
+        assert_eq!(
+            vec![(1, "Rust:", vec![]), (2, "safe, fast, productive.", vec![]), (3, "Pick three.", vec![])],
+            search_case_insensitive("", contents, false)
+        );
+        assert!(search("", contents, true).is_empty());
+        assert!(search_case_insensitive("", contents, true).is_empty());
+    }
+
+    #[test]
+    fn match_count() {
+        let pattern = "duct";
+        let contents = "\
+Rust:
+safe, fast, productive.
+Pick three.
+Very productive indeed.";
+
+        assert_eq!(2, search(pattern, contents, false).len());
+    }
+
+    #[test]
+    fn regex_match() {
+        let pattern = "^Pick (three|four)";
+        let contents = "\
+Rust:
+safe, fast, productive.
+Pick three.";
+
+        assert_eq!(
+            vec![(3, "Pick three.", vec![(0, 10)])],
+            search_regex(pattern, contents, false).unwrap()
+        );
+    }
+
+    #[test]
+    fn regex_invalid_pattern() {
+        let result = search_regex("(unterminated", "some text", false);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn collect_files_skips_non_recursive_directory() {
+        let files = collect_files(&[String::from(".")], false);
+
+        assert!(files.is_empty());
+    }
+
+    #[test]
+    fn collect_files_recursive_walks_nested_directories() {
+        let dir = temp_dir("recursive_walk");
+        let nested = dir.join("nested");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(dir.join("top.txt"), "top").unwrap();
+        fs::write(nested.join("deep.txt"), "deep").unwrap();
+
+        let mut found = collect_files(&[dir.display().to_string()], true);
+        found.sort_by_key(|input| match input {
+            Input::File(path) => path.clone(),
+            Input::Stdin => PathBuf::new()
+        });
+
+        let mut expected = vec![Input::File(dir.join("top.txt")), Input::File(nested.join("deep.txt"))];
+        expected.sort_by_key(|input| match input {
+            Input::File(path) => path.clone(),
+            Input::Stdin => PathBuf::new()
+        });
+
+        assert_eq!(expected, found);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn format_result_prefixes_filename_only_when_multiple() {
+        assert_eq!("hello", format_result("foo.txt", false, false, 1, "hello"));
+        assert_eq!("foo.txt:hello", format_result("foo.txt", true, false, 1, "hello"));
+    }
+
+    #[test]
+    fn format_result_prefixes_line_number() {
+        assert_eq!("foo.txt:3:hello", format_result("foo.txt", true, true, 3, "hello"));
+    }
+
+    #[test]
+    fn run_warns_and_continues_when_one_of_several_files_is_unreadable() {
+        let dir = temp_dir("partial_failure");
+        fs::create_dir_all(&dir).unwrap();
+        let good = dir.join("good.txt");
+        fs::write(&good, "productive\n").unwrap();
+        let missing = dir.join("missing.txt");
+
+        let config = prepare_config_multi("duct", &[
+            good.to_str().unwrap(),
+            missing.to_str().unwrap()
+        ]);
+
+        assert!(run(config).is_ok());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn run_fails_when_no_file_is_readable() {
+        let dir = temp_dir("all_unreadable");
+        let config = prepare_config_multi("duct", &[
+            dir.join("a.txt").to_str().unwrap(),
+            dir.join("b.txt").to_str().unwrap()
+        ]);
+
+        assert!(run(config).is_err());
+    }
+
+    #[test]
+    fn missing_filename_defaults_to_stdin() {
+        let config = prepare_config_no_filename("pattern");
+
+        assert_eq!(config.filenames, vec!["-"]);
+    }
+
+    #[test]
+    fn reader_search_matches_like_a_file() {
+        let reader = io::Cursor::new(&b"Rust:\nsafe, fast, productive.\nPick three.\n"[..]);
+        let contents = read_lines(reader).unwrap();
+        let pattern = "duct";
+        let line = "safe, fast, productive.";
+        let start = line.find(pattern).unwrap();
+
+        assert_eq!(
+            vec![(2, line, vec![(start, start + pattern.len())])],
+            search(pattern, &contents, false)
+        );
+    }
+
+    #[test]
+    fn highlight_wraps_spans_in_ansi_codes() {
+        let line = "safe, fast, productive.";
+        let start = line.find("duct").unwrap();
+        let end = start + "duct".len();
+
+        assert_eq!(
+            format!("safe, fast, pro{}duct{}ive.", COLOR_START, COLOR_END),
+            highlight(line, &[(start, end)])
+        );
+    }
+
+    fn prepare_config(pattern: &str, filename: &str) -> Config {
+        let args = TestArgs::new(pattern, &[filename]);
+        let config = Config::new(args).unwrap_or_else(|err| {
+            panic!("could not construct Config: {}", err);
+        });
+
+        config
+    }
+
+    fn prepare_config_no_filename(pattern: &str) -> Config {
+        let args = TestArgs::new(pattern, &[]);
+        let config = Config::new(args).unwrap_or_else(|err| {
+            panic!("could not construct Config: {}", err);
+        });
+
+        config
+    }
+
+    fn prepare_config_multi(pattern: &str, filenames: &[&str]) -> Config {
+        let args = TestArgs::new(pattern, filenames);
+        let config = Config::new(args).unwrap_or_else(|err| {
+            panic!("could not construct Config: {}", err);
+        });
+
+        config
+    }
+
+    //A scratch directory under the system temp dir, unique per test process.
+    fn temp_dir(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("minigrep_test_{}_{}", name, std::process::id()))
+    }
+}
@@ -1,5 +1,4 @@
 use std::{env, process};
-use minigrep;
 use minigrep::Config;
 
 fn main() {